@@ -0,0 +1,186 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+/// Per-process counter making temp file names unique during atomic writes.
+static TMP_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+/// Default size (in serialized bytes) above which a `BlockExecution.value` is
+/// offloaded to the artifact store instead of being embedded inline in the
+/// run's `traces`.
+pub const DEFAULT_OFFLOAD_THRESHOLD: usize = 64 * 1024;
+
+/// Reference stored inline in place of a large value. The value itself lives in
+/// the artifact store keyed by `hash`; identical outputs share a hash, so
+/// re-runs of the same `app_hash` deduplicate naturally.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct ArtifactRef {
+    pub hash: String,
+    pub size: usize,
+    pub content_type: String,
+}
+
+/// Content-addressed blob store. Keys are the SHA-256 of the bytes, so a `put`
+/// of already-present content is a no-op.
+#[async_trait]
+pub trait ArtifactStore: Send + Sync {
+    async fn put(&self, bytes: &[u8]) -> Result<String>;
+    async fn get(&self, hash: &str) -> Result<Vec<u8>>;
+    async fn exists(&self, hash: &str) -> Result<bool>;
+}
+
+/// Computes the content-address of `bytes`.
+pub fn hash_bytes(bytes: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    hex::encode(hasher.finalize())
+}
+
+/// On-disk artifact store sharding blobs by the first two hex characters of
+/// their hash to keep directory fan-out reasonable.
+pub struct FileArtifactStore {
+    root: PathBuf,
+}
+
+impl FileArtifactStore {
+    pub fn new(root: PathBuf) -> Self {
+        FileArtifactStore { root }
+    }
+
+    fn path_for(&self, hash: &str) -> Result<PathBuf> {
+        if hash.len() < 2 {
+            Err(anyhow!("Invalid artifact hash `{}`", hash))?;
+        }
+        Ok(self.root.join(&hash[0..2]).join(hash))
+    }
+}
+
+#[async_trait]
+impl ArtifactStore for FileArtifactStore {
+    async fn put(&self, bytes: &[u8]) -> Result<String> {
+        let hash = hash_bytes(bytes);
+        let path = self.path_for(&hash)?;
+        // Content-addressed: identical bytes are already present, skip the write.
+        if tokio::fs::try_exists(&path).await? {
+            return Ok(hash);
+        }
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        // Write to a unique temp file and atomically rename into place so a
+        // crash or a concurrent duplicate `put` can never leave a truncated
+        // blob at the content-addressed key.
+        let tmp = path.with_extension(format!(
+            "tmp.{}.{}",
+            std::process::id(),
+            TMP_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+        ));
+        tokio::fs::write(&tmp, bytes).await?;
+        tokio::fs::rename(&tmp, &path).await?;
+        Ok(hash)
+    }
+
+    async fn get(&self, hash: &str) -> Result<Vec<u8>> {
+        let path = self.path_for(hash)?;
+        Ok(tokio::fs::read(&path).await?)
+    }
+
+    async fn exists(&self, hash: &str) -> Result<bool> {
+        Ok(tokio::fs::try_exists(&self.path_for(hash)?).await?)
+    }
+}
+
+/// Offloads `value` to `store` when its serialized form exceeds `threshold`,
+/// returning the `ArtifactRef` to embed inline. Returns `None` when the value
+/// is small enough to keep inline.
+pub async fn maybe_offload(
+    store: &dyn ArtifactStore,
+    value: &Value,
+    threshold: usize,
+) -> Result<Option<ArtifactRef>> {
+    let bytes = serde_json::to_vec(value)?;
+    if bytes.len() <= threshold {
+        return Ok(None);
+    }
+    let hash = store.put(&bytes).await?;
+    Ok(Some(ArtifactRef {
+        hash,
+        size: bytes.len(),
+        content_type: "application/json".to_string(),
+    }))
+}
+
+/// Fetches and deserializes an offloaded value.
+pub async fn hydrate(store: &dyn ArtifactStore, artifact: &ArtifactRef) -> Result<Value> {
+    let bytes = store.get(&artifact.hash).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tmp_root(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("dust-artifacts-test-{}-{}", std::process::id(), name))
+    }
+
+    #[tokio::test]
+    async fn put_get_round_trips() {
+        let root = tmp_root("round-trip");
+        let store = FileArtifactStore::new(root.clone());
+        let hash = store.put(b"hello").await.unwrap();
+        assert_eq!(hash, hash_bytes(b"hello"));
+        assert!(store.exists(&hash).await.unwrap());
+        assert_eq!(store.get(&hash).await.unwrap(), b"hello");
+        let _ = tokio::fs::remove_dir_all(&root).await;
+    }
+
+    #[tokio::test]
+    async fn identical_content_deduplicates() {
+        let root = tmp_root("dedup");
+        let store = FileArtifactStore::new(root.clone());
+        let a = store.put(b"same").await.unwrap();
+        let b = store.put(b"same").await.unwrap();
+        assert_eq!(a, b);
+        let _ = tokio::fs::remove_dir_all(&root).await;
+    }
+
+    #[tokio::test]
+    async fn offload_respects_threshold() {
+        let root = tmp_root("threshold");
+        let store = FileArtifactStore::new(root.clone());
+        let value = json!("x".repeat(256));
+        // Below threshold: kept inline.
+        assert!(maybe_offload(&store, &value, 4096).await.unwrap().is_none());
+        // Above threshold: offloaded and hydratable.
+        let artifact = maybe_offload(&store, &value, 16).await.unwrap().unwrap();
+        assert_eq!(hydrate(&store, &artifact).await.unwrap(), value);
+        let _ = tokio::fs::remove_dir_all(&root).await;
+    }
+}
+
+/// Hydrates every offloaded cell of a run's traces in place, replacing each
+/// `ArtifactRef` with its inline value. Used by `Store::load_run` when the
+/// caller asks for a fully-materialized run rather than references only.
+pub async fn hydrate_traces(
+    store: &dyn ArtifactStore,
+    traces: &mut [(
+        (crate::blocks::block::BlockType, String),
+        Vec<Vec<crate::run::BlockExecution>>,
+    )],
+) -> Result<()> {
+    for (_, inputs) in traces.iter_mut() {
+        for maps in inputs.iter_mut() {
+            for execution in maps.iter_mut() {
+                if let Some(artifact) = execution.artifact.take() {
+                    execution.value = Some(hydrate(store, &artifact).await?);
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,193 @@
+use crate::run::Status;
+use anyhow::Result;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Process-wide HTTP client. `reqwest::Client` owns a connection pool and is
+/// cheap to clone (it is `Arc` internally), so every `Notifier` shares this one
+/// rather than spinning up a pool per status transition.
+fn shared_client() -> reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(reqwest::Client::new).clone()
+}
+
+/// Header carrying the hex-encoded HMAC-SHA256 signature of the POST body when
+/// a notifier is configured with a signing secret.
+pub const SIGNATURE_HEADER: &str = "X-Dust-Signature";
+
+/// Which transitions a notifier cares about. An empty `statuses` list matches
+/// every status; an empty `blocks` list matches every block (and run-level
+/// transitions, which carry no block name).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct NotifierFilter {
+    #[serde(default)]
+    pub statuses: Vec<Status>,
+    #[serde(default)]
+    pub blocks: Vec<String>,
+}
+
+impl NotifierFilter {
+    fn matches(&self, status: &Status, block_name: Option<&str>) -> bool {
+        if !self.statuses.is_empty() && !self.statuses.contains(status) {
+            return false;
+        }
+        if !self.blocks.is_empty() {
+            match block_name {
+                Some(name) if self.blocks.iter().any(|b| b == name) => {}
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+/// A single webhook target: where to POST, how to sign, and what to report.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NotifierConfig {
+    pub url: String,
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub filter: NotifierFilter,
+}
+
+/// Payload POSTed on every matching transition.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NotificationPayload {
+    pub run_id: String,
+    pub app_hash: String,
+    pub block_type: Option<String>,
+    pub block_name: Option<String>,
+    pub status: Status,
+    pub success_count: usize,
+    pub error_count: usize,
+}
+
+/// Maximum number of delivery attempts before a notification is dropped.
+const MAX_ATTEMPTS: u32 = 4;
+/// Base backoff; the delay doubles on each retry.
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Fires notifications to the configured targets. Deliveries run on a detached
+/// task with retry/backoff so they never block block execution.
+#[derive(Clone)]
+pub struct Notifier {
+    configs: Vec<NotifierConfig>,
+    client: reqwest::Client,
+}
+
+impl Notifier {
+    pub fn new(configs: Vec<NotifierConfig>) -> Self {
+        Notifier {
+            configs,
+            client: shared_client(),
+        }
+    }
+
+    /// Dispatches `payload` to every notifier whose filter matches. Returns
+    /// immediately; each delivery is spawned and retried independently.
+    pub fn notify(&self, payload: NotificationPayload) {
+        for config in &self.configs {
+            if !config
+                .filter
+                .matches(&payload.status, payload.block_name.as_deref())
+            {
+                continue;
+            }
+            let config = config.clone();
+            let client = self.client.clone();
+            let payload = payload.clone();
+            tokio::spawn(async move {
+                if let Err(e) = deliver(&client, &config, &payload).await {
+                    crate::utils::error(&format!(
+                        "Notifier delivery to {} failed: {}",
+                        config.url, e
+                    ));
+                }
+            });
+        }
+    }
+}
+
+async fn deliver(
+    client: &reqwest::Client,
+    config: &NotifierConfig,
+    payload: &NotificationPayload,
+) -> Result<()> {
+    let body = serde_json::to_vec(payload)?;
+
+    let mut backoff = BASE_BACKOFF;
+    let mut last_err = None;
+    for _ in 0..MAX_ATTEMPTS {
+        let mut req = client
+            .post(&config.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+
+        if let Some(secret) = &config.secret {
+            req = req.header(SIGNATURE_HEADER, sign(secret, &body));
+        }
+
+        match req.body(body.clone()).send().await {
+            Ok(res) if res.status().is_success() => return Ok(()),
+            Ok(res) => last_err = Some(anyhow::anyhow!("status {}", res.status())),
+            Err(e) => last_err = Some(e.into()),
+        }
+
+        tokio::time::sleep(backoff).await;
+        backoff *= 2;
+    }
+
+    Err(last_err.unwrap_or_else(|| anyhow::anyhow!("delivery failed")))
+}
+
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .expect("HMAC can take a key of any size");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_filter_matches_everything() {
+        let f = NotifierFilter::default();
+        assert!(f.matches(&Status::Succeeded, Some("root")));
+        assert!(f.matches(&Status::Errored, None));
+    }
+
+    #[test]
+    fn status_filter_restricts() {
+        let f = NotifierFilter {
+            statuses: vec![Status::Errored],
+            blocks: vec![],
+        };
+        assert!(f.matches(&Status::Errored, Some("root")));
+        assert!(!f.matches(&Status::Succeeded, Some("root")));
+    }
+
+    #[test]
+    fn block_filter_requires_named_block() {
+        let f = NotifierFilter {
+            statuses: vec![],
+            blocks: vec!["final".to_string()],
+        };
+        assert!(f.matches(&Status::Succeeded, Some("final")));
+        assert!(!f.matches(&Status::Succeeded, Some("other")));
+        // Run-level transitions carry no block name and are filtered out.
+        assert!(!f.matches(&Status::Succeeded, None));
+    }
+
+    #[test]
+    fn signature_is_stable_for_body_and_secret() {
+        assert_eq!(sign("s3cret", b"payload"), sign("s3cret", b"payload"));
+        assert_ne!(sign("s3cret", b"payload"), sign("other", b"payload"));
+    }
+}
@@ -1,6 +1,10 @@
 use crate::blocks::block::BlockType;
+use crate::artifacts::{hydrate, maybe_offload, ArtifactRef, ArtifactStore, FileArtifactStore};
+use crate::notifier::{NotificationPayload, Notifier, NotifierConfig};
 use crate::project::Project;
-use crate::stores::{sqlite::SQLiteStore, store::Store};
+use crate::run_watch::{watch_run, WatchToken};
+use crate::stores::builder::store_from_env;
+use crate::stores::store::Store;
 use crate::utils;
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
@@ -12,11 +16,16 @@ use std::str::FromStr;
 /// - `env` used
 /// - `value` returned by successful execution
 /// - `error` message returned by a failed execution
+/// - `artifact` reference when a large `value` was offloaded to the artifact
+///   store instead of being embedded inline (see `artifacts`); when set,
+///   `value` is `None` until the execution is hydrated.
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct BlockExecution {
     // pub env: Env,
     pub value: Option<Value>,
     pub error: Option<String>,
+    #[serde(default)]
+    pub artifact: Option<ArtifactRef>,
 }
 
 pub type Credentials = HashMap<String, String>;
@@ -24,6 +33,9 @@ pub type Credentials = HashMap<String, String>;
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct RunConfig {
     pub blocks: HashMap<String, Value>,
+    // Optional webhook targets notified on run and block status transitions.
+    #[serde(default)]
+    pub notifiers: Vec<NotifierConfig>,
 }
 
 impl RunConfig {
@@ -57,6 +69,7 @@ impl RunConfig {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum Status {
+    Queued,
     Running,
     Succeeded,
     Errored,
@@ -65,6 +78,7 @@ pub enum Status {
 impl ToString for Status {
     fn to_string(&self) -> String {
         match self {
+            Status::Queued => "queued".to_string(),
             Status::Running => "running".to_string(),
             Status::Succeeded => "succeeded".to_string(),
             Status::Errored => "errored".to_string(),
@@ -76,6 +90,7 @@ impl FromStr for Status {
     type Err = utils::ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
+            "queued" => Ok(Status::Queued),
             "running" => Ok(Status::Running),
             "succeeded" => Ok(Status::Succeeded),
             "errored" => Ok(Status::Errored),
@@ -122,6 +137,12 @@ impl RunStatus {
     pub fn run_status(&self) -> Status {
         self.run.clone()
     }
+
+    pub fn block_status(&self, block_type: &BlockType, name: &str) -> Option<&BlockStatus> {
+        self.blocks
+            .iter()
+            .find(|s| s.block_type == *block_type && s.name == name)
+    }
 }
 
 /// Execution represents the full execution of an app on input data.
@@ -203,18 +224,105 @@ impl Run {
     }
 
     pub fn set_run_status(&mut self, status: Status) {
-        self.status.run = status;
+        self.status.run = status.clone();
+        self.notify(NotificationPayload {
+            run_id: self.run_id.clone(),
+            app_hash: self.app_hash.clone(),
+            block_type: None,
+            block_name: None,
+            status,
+            success_count: 0,
+            error_count: 0,
+        });
     }
 
     pub fn set_block_status(&mut self, status: BlockStatus) {
+        // Only notify when the block's status actually changes, so a Map/Reduce
+        // over many cells doesn't emit a spurious webhook per unchanged update.
+        let changed = !self
+            .status
+            .blocks
+            .iter()
+            .any(|s| s.block_type == status.block_type && s.name == status.name && *s == status);
+        if changed {
+            self.notify(NotificationPayload {
+                run_id: self.run_id.clone(),
+                app_hash: self.app_hash.clone(),
+                block_type: Some(status.block_type.to_string()),
+                block_name: Some(status.name.clone()),
+                status: status.status.clone(),
+                success_count: status.success_count,
+                error_count: status.error_count,
+            });
+        }
         self.status.set_block_status(status);
     }
+
+    /// Records a completed `BlockExecution` into `traces` at
+    /// `(input_idx, map_idx)`, offloading its value to `artifacts` first when
+    /// the serialized form exceeds `threshold`. Offloaded cells keep only an
+    /// `ArtifactRef` inline, so `traces` stays small even for a Map over
+    /// thousands of large outputs. Called by the executor as each cell finishes.
+    pub async fn record_execution(
+        &mut self,
+        artifacts: &dyn ArtifactStore,
+        block: (BlockType, String),
+        input_idx: usize,
+        map_idx: usize,
+        mut execution: BlockExecution,
+        threshold: usize,
+    ) -> Result<()> {
+        if let Some(value) = execution.value.as_ref() {
+            if let Some(artifact) = maybe_offload(artifacts, value, threshold).await? {
+                execution.value = None;
+                execution.artifact = Some(artifact);
+            }
+        }
+
+        let inputs = match self.traces.iter_mut().find(|((t, n), _)| *t == block.0 && *n == block.1)
+        {
+            Some((_, inputs)) => inputs,
+            None => {
+                self.traces.push((block, vec![]));
+                &mut self.traces.last_mut().unwrap().1
+            }
+        };
+        if inputs.len() <= input_idx {
+            inputs.resize(input_idx + 1, vec![]);
+        }
+        let maps = &mut inputs[input_idx];
+        if maps.len() <= map_idx {
+            maps.resize(
+                map_idx + 1,
+                BlockExecution {
+                    value: None,
+                    error: None,
+                    artifact: None,
+                },
+            );
+        }
+        maps[map_idx] = execution;
+        Ok(())
+    }
+
+    /// Fires a notification to the run's configured webhook targets (no-op when
+    /// none are configured). Delivery is async and never blocks execution.
+    fn notify(&self, payload: NotificationPayload) {
+        if self.config.notifiers.is_empty() {
+            return;
+        }
+        Notifier::new(self.config.notifiers.clone()).notify(payload);
+    }
 }
 
-pub async fn cmd_inspect(run_id: &str, block_type: BlockType, block_name: &str) -> Result<()> {
+pub async fn cmd_inspect(
+    run_id: &str,
+    block_type: BlockType,
+    block_name: &str,
+    follow: bool,
+) -> Result<()> {
     let root_path = utils::init_check().await?;
-    let store = SQLiteStore::new(root_path.join("store.sqlite"))?;
-    store.init().await?;
+    let store = store_from_env(root_path.join("store.sqlite")).await?;
     let project = Project::new_from_id(1);
 
     let mut run_id = run_id.to_string();
@@ -227,11 +335,27 @@ pub async fn cmd_inspect(run_id: &str, block_type: BlockType, block_name: &str)
         utils::info(&format!("Latest run is `{}`", run_id));
     }
 
+    let artifacts = FileArtifactStore::new(root_path.join("artifacts"));
+
+    if follow {
+        return cmd_inspect_follow(
+            store.as_ref(),
+            &artifacts,
+            &project,
+            &run_id,
+            block_type,
+            block_name,
+        )
+        .await;
+    }
+
     let run = match store
         .load_run(
             &project,
             &run_id,
             Some(Some((block_type, block_name.to_string()))),
+            // References only: cmd_inspect hydrates the specific cell it prints.
+            false,
         )
         .await?
     {
@@ -240,36 +364,32 @@ pub async fn cmd_inspect(run_id: &str, block_type: BlockType, block_name: &str)
     };
 
     let mut found = false;
-    run.traces.iter().for_each(|((t, n), input_executions)| {
-        if n == block_name && *t == block_type {
-            input_executions
-                .iter()
-                .enumerate()
-                .for_each(|(input_idx, map_executions)| {
-                    map_executions
-                        .iter()
-                        .enumerate()
-                        .for_each(|(map_idx, execution)| {
-                            found = true;
-                            utils::info(&format!(
-                                "Execution: input_idx={}/{} map_idx={}/{}",
-                                input_idx,
-                                input_executions.len(),
-                                map_idx,
-                                map_executions.len()
-                            ));
-                            match execution.value.as_ref() {
-                                Some(v) => println!("{}", to_string_pretty(v).unwrap()),
-                                None => {}
-                            }
-                            match execution.error.as_ref() {
-                                Some(e) => utils::error(&format!("Error: {}", e)),
-                                None => {}
-                            }
-                        });
-                });
+    for ((t, n), input_executions) in run.traces.iter() {
+        if n != block_name || *t != block_type {
+            continue;
         }
-    });
+        for (input_idx, map_executions) in input_executions.iter().enumerate() {
+            for (map_idx, execution) in map_executions.iter().enumerate() {
+                found = true;
+                utils::info(&format!(
+                    "Execution: input_idx={}/{} map_idx={}/{}",
+                    input_idx,
+                    input_executions.len(),
+                    map_idx,
+                    map_executions.len()
+                ));
+                // Transparently fetch the blob for this specific cell when its
+                // value was offloaded to the artifact store.
+                match resolve_value(&artifacts, execution).await? {
+                    Some(v) => println!("{}", to_string_pretty(&v).unwrap()),
+                    None => {}
+                }
+                if let Some(e) = execution.error.as_ref() {
+                    utils::error(&format!("Error: {}", e));
+                }
+            }
+        }
+    }
 
     if !found {
         Err(anyhow!(
@@ -283,10 +403,77 @@ pub async fn cmd_inspect(run_id: &str, block_type: BlockType, block_name: &str)
     Ok(())
 }
 
+/// Returns the execution's value, hydrating it from the artifact store when it
+/// was offloaded. An inline value takes precedence; an execution with neither
+/// value nor artifact (e.g. skipped by a conditional) yields `None`.
+async fn resolve_value(
+    artifacts: &dyn ArtifactStore,
+    execution: &BlockExecution,
+) -> Result<Option<Value>> {
+    if let Some(v) = execution.value.as_ref() {
+        return Ok(Some(v.clone()));
+    }
+    match execution.artifact.as_ref() {
+        Some(artifact) => Ok(Some(hydrate(artifacts, artifact).await?)),
+        None => Ok(None),
+    }
+}
+
+/// Streams executions for a single block as the run progresses, looping on
+/// `watch_run` and printing each `(input_idx, map_idx)` cell the moment it is
+/// persisted. Returns once the run leaves `Status::Running`.
+async fn cmd_inspect_follow(
+    store: &dyn Store,
+    artifacts: &dyn ArtifactStore,
+    project: &Project,
+    run_id: &str,
+    block_type: BlockType,
+    block_name: &str,
+) -> Result<()> {
+    // Generous per-call timeout: an empty batch just means re-poll.
+    let timeout = std::time::Duration::from_secs(30);
+    let mut token: WatchToken = 0;
+
+    loop {
+        let batch = watch_run(
+            store,
+            project,
+            run_id,
+            &block_type,
+            block_name,
+            token,
+            timeout,
+        )
+        .await?;
+        token = batch.token;
+
+        // watch_run already returns only the cells completed since `token`.
+        for e in batch.executions {
+            utils::info(&format!(
+                "Execution: input_idx={} map_idx={}",
+                e.input_idx, e.map_idx
+            ));
+            if let Some(v) = resolve_value(artifacts, &e.execution).await? {
+                println!("{}", to_string_pretty(&v).unwrap());
+            }
+            if let Some(err) = e.execution.error.as_ref() {
+                utils::error(&format!("Error: {}", err));
+            }
+        }
+
+        // The batch already carries the run-level status, so no extra load is
+        // needed to decide when to stop.
+        if batch.run_status != Status::Running {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
 pub async fn cmd_list() -> Result<()> {
     let root_path = utils::init_check().await?;
-    let store = SQLiteStore::new(root_path.join("store.sqlite"))?;
-    store.init().await?;
+    let store = store_from_env(root_path.join("store.sqlite")).await?;
     let project = Project::new_from_id(1);
 
     store
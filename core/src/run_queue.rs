@@ -0,0 +1,229 @@
+use crate::project::Project;
+use crate::run::{Run, Status};
+use crate::stores::store::Store;
+use crate::utils;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Status of a row in the `job_queue` table. A job is `New` until a worker
+/// claims it atomically, at which point it flips to `Running` and starts
+/// reporting a `heartbeat`. Terminal states live on the `Run` itself
+/// (`Status::Succeeded` / `Status::Errored`); a claimed row is deleted once the
+/// run reaches one of them.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    New,
+    Running,
+}
+
+impl ToString for JobStatus {
+    fn to_string(&self) -> String {
+        match self {
+            JobStatus::New => "new".to_string(),
+            JobStatus::Running => "running".to_string(),
+        }
+    }
+}
+
+impl FromStr for JobStatus {
+    type Err = utils::ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "new" => Ok(JobStatus::New),
+            "running" => Ok(JobStatus::Running),
+            _ => Err(utils::ParseError::with_message("Unknown JobStatus"))?,
+        }
+    }
+}
+
+/// A claimed unit of work pointing back at a persisted `Run`. `claimed_by`
+/// identifies the worker currently responsible for the run and `heartbeat` is
+/// the last time that worker proved liveness; the reaper uses it to recover
+/// runs stranded by a crashed worker.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QueuedJob {
+    pub run_id: String,
+    pub queue_name: String,
+    pub status: JobStatus,
+    pub heartbeat: u64,
+    pub claimed_by: Option<String>,
+}
+
+/// How often a worker refreshes `heartbeat` while executing a run's blocks.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Default age past which a `Running` job is considered stale and reset to
+/// `new` by the reaper.
+const DEFAULT_STALE_AFTER_SECONDS: u64 = 60;
+
+/// Durable job queue layered on top of a `Store`. Backends implement the five
+/// primitives below; everything else (the worker loop, the reaper) is shared.
+///
+/// The claim is expected to be atomic. For SQLite this is a single write
+/// transaction:
+///
+/// ```sql
+/// UPDATE job_queue SET status = 'running', heartbeat = ?, claimed_by = ?
+/// WHERE run_id = (
+///     SELECT run_id FROM job_queue WHERE queue_name = ? AND status = 'new'
+///     ORDER BY created ASC LIMIT 1
+/// )
+/// RETURNING run_id, queue_name, status, heartbeat, claimed_by
+/// ```
+///
+/// A pooled/postgres backend can do the same without serializing all workers
+/// by selecting the candidate row `FOR UPDATE SKIP LOCKED`. Only `SQLiteStore`
+/// implements `JobQueue`; `PostgresStore` does not.
+#[async_trait]
+pub trait JobQueue {
+    /// Registers `run` on `queue_name` as a `new` job and persists it with
+    /// `Status::Queued`.
+    async fn enqueue(&self, project: &Project, run: &Run, queue_name: &str) -> Result<()>;
+
+    /// Atomically claims the oldest `new` job on `queue_name` for `worker_id`,
+    /// flipping it to `running` and stamping the heartbeat. Returns `None` when
+    /// the queue is empty.
+    async fn claim(&self, queue_name: &str, worker_id: &str) -> Result<Option<QueuedJob>>;
+
+    /// Refreshes the heartbeat of a claimed job.
+    async fn heartbeat(&self, run_id: &str) -> Result<()>;
+
+    /// Removes a job once its run reaches a terminal state.
+    async fn complete(&self, run_id: &str) -> Result<()>;
+
+    /// Resets every `running` job whose heartbeat is older than
+    /// `stale_after_seconds` back to `new` and clears its `claimed_by`, so
+    /// another worker re-executes the run from the last persisted `traces`.
+    /// Returns the recovered `run_id`s.
+    async fn reap(&self, stale_after_seconds: u64) -> Result<Vec<String>>;
+}
+
+/// A worker drains a single queue: it claims runs one at a time, executes their
+/// blocks while keeping the heartbeat fresh, and completes the job. Execution
+/// resumes from whatever `traces` were already persisted, so a run picked up
+/// after a crash replays only the blocks that never finished (at-least-once).
+pub struct Worker<S: Store + JobQueue + Clone + Send + Sync + 'static> {
+    store: S,
+    worker_id: String,
+    queue_name: String,
+}
+
+impl<S: Store + JobQueue + Clone + Send + Sync + 'static> Worker<S> {
+    pub fn new(store: S, queue_name: &str) -> Self {
+        Worker {
+            store,
+            worker_id: utils::new_id(),
+            queue_name: queue_name.to_string(),
+        }
+    }
+
+    pub fn worker_id(&self) -> &str {
+        &self.worker_id
+    }
+
+    /// Claims and executes the next queued run, if any. Returns the executed
+    /// `run_id`, or `None` when the queue was empty.
+    pub async fn run_once(&self, project: &Project) -> Result<Option<String>> {
+        let job = match self.store.claim(&self.queue_name, &self.worker_id).await? {
+            Some(job) => job,
+            None => return Ok(None),
+        };
+
+        let mut run = match self.store.load_run(project, &job.run_id, None, true).await? {
+            Some(r) => r,
+            None => Err(anyhow!("Claimed run `{}` not found", job.run_id))?,
+        };
+
+        run.set_run_status(Status::Running);
+        self.store.store_run(project, &run).await?;
+
+        // Execute the run while spawning a heartbeat so the reaper leaves us
+        // alone for as long as we make progress.
+        let result = self.execute_with_heartbeat(project, &mut run).await;
+
+        run.set_run_status(match result {
+            Ok(()) => Status::Succeeded,
+            Err(_) => Status::Errored,
+        });
+        self.store.store_run(project, &run).await?;
+        self.store.complete(&job.run_id).await?;
+
+        Ok(Some(job.run_id))
+    }
+
+    /// Continuously claims and executes runs, yielding briefly whenever the
+    /// queue is momentarily empty. Runs until the process is stopped.
+    pub async fn run_forever(&self, project: &Project) -> Result<()> {
+        loop {
+            if self.run_once(project).await?.is_none() {
+                tokio::time::sleep(HEARTBEAT_INTERVAL).await;
+            }
+        }
+    }
+
+    async fn execute_with_heartbeat(&self, project: &Project, run: &mut Run) -> Result<()> {
+        let run_id = run.run_id().to_string();
+        let store = self.store.clone();
+        let beat = tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(HEARTBEAT_INTERVAL).await;
+                if store.heartbeat(&run_id).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let result = run.execute(project, &self.store).await;
+
+        beat.abort();
+        result
+    }
+}
+
+/// Background reaper that periodically resets stale `running` jobs so crashed
+/// workers don't strand runs forever.
+pub struct Reaper<S: JobQueue> {
+    store: S,
+    stale_after_seconds: u64,
+}
+
+impl<S: JobQueue> Reaper<S> {
+    pub fn new(store: S) -> Self {
+        Reaper {
+            store,
+            stale_after_seconds: DEFAULT_STALE_AFTER_SECONDS,
+        }
+    }
+
+    pub fn with_stale_after(mut self, stale_after_seconds: u64) -> Self {
+        self.stale_after_seconds = stale_after_seconds;
+        self
+    }
+
+    pub async fn run_forever(&self) -> Result<()> {
+        loop {
+            let recovered = self.store.reap(self.stale_after_seconds).await?;
+            for run_id in recovered {
+                utils::info(&format!("Reaped stale run `{}`", run_id));
+            }
+            tokio::time::sleep(Duration::from_secs(self.stale_after_seconds / 2)).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn job_status_round_trips() {
+        for s in [JobStatus::New, JobStatus::Running] {
+            assert_eq!(JobStatus::from_str(&s.to_string()).unwrap(), s);
+        }
+        assert!(JobStatus::from_str("bogus").is_err());
+    }
+}
@@ -0,0 +1,188 @@
+use crate::blocks::block::BlockType;
+use crate::project::Project;
+use crate::run::{BlockExecution, Status};
+use crate::stores::store::Store;
+use anyhow::{anyhow, Result};
+use std::time::{Duration, Instant};
+
+/// Monotonic token identifying a point in a watched block's execution history:
+/// the number of completed cells. It advances by one per newly-finished cell,
+/// so a client holding a token can ask for everything that completed since.
+pub type WatchToken = u64;
+
+/// A single completed cell, flattened out of the nested `traces` structure with
+/// enough coordinates for a follower to locate it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchedExecution {
+    pub block_type: BlockType,
+    pub block_name: String,
+    pub input_idx: usize,
+    pub map_idx: usize,
+    pub execution: BlockExecution,
+}
+
+/// Result of a `watch_run` call: every completed cell for the watched block and
+/// the current token, plus the run-level status so callers can decide when to
+/// stop without a second load. The batch is empty when nothing advanced past
+/// the caller's token (e.g. on timeout) so the client immediately re-polls.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchBatch {
+    pub token: WatchToken,
+    pub run_status: Status,
+    pub executions: Vec<WatchedExecution>,
+}
+
+/// Interval between store polls while waiting for the token to advance.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+fn is_complete(execution: &BlockExecution) -> bool {
+    execution.value.is_some() || execution.artifact.is_some() || execution.error.is_some()
+}
+
+/// Flattens the completed cells of a single block. Cells still in flight
+/// (neither value, artifact, nor error) are skipped so a half-filled
+/// `Vec<Vec<_>>` from concurrent map execution doesn't surface placeholders.
+fn completed_cells(
+    block_type: &BlockType,
+    block_name: &str,
+    inputs: &[Vec<BlockExecution>],
+) -> Vec<WatchedExecution> {
+    let mut out = vec![];
+    for (input_idx, maps) in inputs.iter().enumerate() {
+        for (map_idx, execution) in maps.iter().enumerate() {
+            if !is_complete(execution) {
+                continue;
+            }
+            out.push(WatchedExecution {
+                block_type: block_type.clone(),
+                block_name: block_name.to_string(),
+                input_idx,
+                map_idx,
+                execution: execution.clone(),
+            });
+        }
+    }
+    out
+}
+
+/// Blocks until the watched block's token advances past `since_token` or
+/// `timeout` elapses, then returns only the cells completed since `since_token`
+/// plus the current token. The run is loaded with its traces filtered to the
+/// watched block. On timeout the batch is empty (but the token and run status are
+/// still current), so the client can loop without losing its place.
+pub async fn watch_run(
+    store: &dyn Store,
+    project: &Project,
+    run_id: &str,
+    block_type: &BlockType,
+    block_name: &str,
+    since_token: WatchToken,
+    timeout: Duration,
+) -> Result<WatchBatch> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let run = store
+            .load_run(
+                project,
+                run_id,
+                Some(Some((block_type.clone(), block_name.to_string()))),
+                // References only; the follower hydrates each printed cell.
+                false,
+            )
+            .await?
+            .ok_or_else(|| anyhow!("Run with id {} not found", run_id))?;
+        let run_status = run.status().run_status();
+
+        let completed = run
+            .traces
+            .iter()
+            .find(|((t, n), _)| t == block_type && n == block_name)
+            .map(|((t, n), inputs)| completed_cells(t, n, inputs))
+            .unwrap_or_default();
+
+        // The token is exactly the number of completed cells, so it advances by
+        // one per newly-finished cell and never runs ahead of the cells we can
+        // actually return.
+        let token = completed.len() as WatchToken;
+
+        if token > since_token {
+            // Ship only the delta past the caller's token, not the whole trace.
+            let executions = completed
+                .into_iter()
+                .skip(since_token as usize)
+                .collect::<Vec<_>>();
+            return Ok(WatchBatch {
+                token,
+                run_status,
+                executions,
+            });
+        }
+
+        // Nothing new; stop early once the run is no longer running or the
+        // timeout is hit, otherwise poll again.
+        if run_status != Status::Running || Instant::now() >= deadline {
+            return Ok(WatchBatch {
+                token,
+                run_status,
+                executions: vec![],
+            });
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn done(value: serde_json::Value) -> BlockExecution {
+        BlockExecution {
+            value: Some(value),
+            error: None,
+            artifact: None,
+        }
+    }
+
+    fn pending() -> BlockExecution {
+        BlockExecution {
+            value: None,
+            error: None,
+            artifact: None,
+        }
+    }
+
+    #[test]
+    fn completed_cells_skips_in_flight() {
+        // Input 0 finished, input 1 still running (placeholder from resize).
+        let inputs = vec![vec![done(json!("a"))], vec![pending()]];
+        let cells = completed_cells(&BlockType::Input, "root", &inputs);
+        assert_eq!(cells.len(), 1);
+        assert_eq!(cells[0].input_idx, 0);
+        assert_eq!(cells[0].map_idx, 0);
+    }
+
+    #[test]
+    fn token_tracks_completed_cell_count() {
+        // The token the watcher computes is exactly the completed-cell count,
+        // so it advances by one per finished cell regardless of block status.
+        let mut inputs = vec![vec![pending()], vec![pending()]];
+        assert_eq!(completed_cells(&BlockType::Input, "root", &inputs).len(), 0);
+        inputs[1][0] = done(json!("b"));
+        assert_eq!(completed_cells(&BlockType::Input, "root", &inputs).len(), 1);
+        inputs[0][0] = done(json!("a"));
+        assert_eq!(completed_cells(&BlockType::Input, "root", &inputs).len(), 2);
+    }
+
+    #[test]
+    fn delta_past_token_is_the_tail() {
+        let inputs = vec![vec![done(json!("a"))], vec![done(json!("b"))]];
+        let all = completed_cells(&BlockType::Input, "root", &inputs);
+        let token = all.len() as WatchToken;
+        // A caller holding token=1 gets just the second cell back.
+        let delta: Vec<_> = all.into_iter().skip(1).collect();
+        assert_eq!(token, 2);
+        assert_eq!(delta.len(), 1);
+        assert_eq!(delta[0].execution.value, Some(json!("b")));
+    }
+}
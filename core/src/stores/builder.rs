@@ -0,0 +1,44 @@
+use crate::stores::postgres::PostgresStore;
+use crate::stores::sqlite::SQLiteStore;
+use crate::stores::store::Store;
+use anyhow::{anyhow, Result};
+use std::path::PathBuf;
+
+/// Environment variable selecting the store backend: `sqlite` (default) or
+/// `postgres`. When set to `postgres`, `DUST_DATABASE_URI` must hold the
+/// connection string.
+pub const STORE_ENV: &str = "DUST_STORE";
+pub const DATABASE_URI_ENV: &str = "DUST_DATABASE_URI";
+
+/// Backend the commands persist to. Mirrors `STORE_ENV` so the choice can also
+/// be threaded from a CLI flag.
+pub enum StoreBackend {
+    SQLite,
+    Postgres,
+}
+
+impl StoreBackend {
+    pub fn from_env() -> Result<Self> {
+        match std::env::var(STORE_ENV).ok().as_deref() {
+            None | Some("sqlite") => Ok(StoreBackend::SQLite),
+            Some("postgres") => Ok(StoreBackend::Postgres),
+            Some(other) => Err(anyhow!("Unknown {} value `{}`", STORE_ENV, other)),
+        }
+    }
+}
+
+/// Builds the configured store as a trait object so `cmd_inspect`/`cmd_list`
+/// (and the executor) work transparently against either backend. `sqlite_path`
+/// is only used by the SQLite backend.
+pub async fn store_from_env(sqlite_path: PathBuf) -> Result<Box<dyn Store>> {
+    let store: Box<dyn Store> = match StoreBackend::from_env()? {
+        StoreBackend::SQLite => Box::new(SQLiteStore::new(sqlite_path)?),
+        StoreBackend::Postgres => {
+            let uri = std::env::var(DATABASE_URI_ENV)
+                .map_err(|_| anyhow!("{} must be set when {}=postgres", DATABASE_URI_ENV, STORE_ENV))?;
+            Box::new(PostgresStore::new(&uri)?)
+        }
+    };
+    store.init().await?;
+    Ok(store)
+}
@@ -0,0 +1,222 @@
+use crate::artifacts::{hydrate_traces, ArtifactStore};
+use crate::project::Project;
+use crate::run::{Run, RunConfig, RunStatus, Status};
+use crate::stores::store::Store;
+use anyhow::Result;
+use async_trait::async_trait;
+use deadpool_postgres::{Manager, ManagerConfig, Pool, RecyclingMethod};
+use serde_json::Value;
+use std::sync::Arc;
+use tokio_postgres::NoTls;
+
+/// Postgres-backed `Store`. Unlike `SQLiteStore`, whose pooled connections all
+/// share one local database file, this backend pools connections and relies on
+/// row-level locking so multiple workers on different machines can execute and
+/// persist runs concurrently against shared history.
+///
+/// `RunConfig`, `RunStatus` and `traces` are stored as `JSONB`; `Status` maps
+/// to the native `job_status` enum declared in `init`.
+#[derive(Clone)]
+pub struct PostgresStore {
+    pool: Pool,
+    // See `SQLiteStore::artifacts`.
+    artifacts: Option<Arc<dyn ArtifactStore>>,
+}
+
+impl PostgresStore {
+    pub fn new(uri: &str) -> Result<Self> {
+        let pg_config: tokio_postgres::Config = uri.parse()?;
+        let mgr = Manager::from_config(
+            pg_config,
+            NoTls,
+            ManagerConfig {
+                recycling_method: RecyclingMethod::Fast,
+            },
+        );
+        let pool = Pool::builder(mgr).build()?;
+        Ok(PostgresStore {
+            pool,
+            artifacts: None,
+        })
+    }
+
+    pub fn with_artifacts(mut self, artifacts: Arc<dyn ArtifactStore>) -> Self {
+        self.artifacts = Some(artifacts);
+        self
+    }
+}
+
+#[async_trait]
+impl Store for PostgresStore {
+    async fn init(&self) -> Result<()> {
+        let c = self.pool.get().await?;
+        // Native enum mirroring `run::Status`; created idempotently.
+        c.batch_execute(
+            "DO $$ BEGIN
+                 CREATE TYPE job_status AS ENUM ('queued', 'running', 'succeeded', 'errored');
+             EXCEPTION WHEN duplicate_object THEN NULL; END $$;
+             CREATE TABLE IF NOT EXISTS runs (
+                 id          BIGSERIAL PRIMARY KEY,
+                 project     BIGINT NOT NULL,
+                 run_id      TEXT NOT NULL,
+                 created     BIGINT NOT NULL,
+                 app_hash    TEXT NOT NULL,
+                 config      JSONB NOT NULL,
+                 status      job_status NOT NULL,
+                 run_status  JSONB NOT NULL,
+                 traces      JSONB NOT NULL,
+                 UNIQUE (project, run_id)
+             );
+             CREATE INDEX IF NOT EXISTS runs_project_created ON runs (project, created DESC);",
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn latest_run_id(&self, project: &Project) -> Result<Option<String>> {
+        let c = self.pool.get().await?;
+        let row = c
+            .query_opt(
+                "SELECT run_id FROM runs WHERE project = $1 ORDER BY created DESC LIMIT 1",
+                &[&project.project_id()],
+            )
+            .await?;
+        Ok(row.map(|r| r.get::<_, String>(0)))
+    }
+
+    async fn load_run(
+        &self,
+        project: &Project,
+        run_id: &str,
+        // See `SQLiteStore::load_run`: `None` loads all traces, `Some(None)`
+        // loads none, `Some(Some(block))` loads a single block's traces.
+        block: Option<Option<(crate::blocks::block::BlockType, String)>>,
+        hydrate: bool,
+    ) -> Result<Option<Run>> {
+        let c = self.pool.get().await?;
+        let row = match c
+            .query_opt(
+                "SELECT created, app_hash, config, run_status, traces
+                 FROM runs WHERE project = $1 AND run_id = $2",
+                &[&project.project_id(), &run_id],
+            )
+            .await?
+        {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+
+        let created: i64 = row.get(0);
+        let app_hash: String = row.get(1);
+        let config: RunConfig = serde_json::from_value(row.get::<_, Value>(2))?;
+        let status: RunStatus = serde_json::from_value(row.get::<_, Value>(3))?;
+        let mut traces: Vec<(
+            (crate::blocks::block::BlockType, String),
+            Vec<Vec<crate::run::BlockExecution>>,
+        )> = serde_json::from_value(row.get::<_, Value>(4))?;
+
+        // Apply the same block projection SQLiteStore uses so callers see an
+        // identical view regardless of backend.
+        if let Some(filter) = block {
+            traces.retain(|((t, n), _)| match &filter {
+                Some((bt, bn)) => t == bt && n == bn,
+                None => false,
+            });
+        }
+
+        if hydrate {
+            if let Some(artifacts) = &self.artifacts {
+                hydrate_traces(artifacts.as_ref(), &mut traces).await?;
+            }
+        }
+
+        Ok(Some(Run::new_from_store(
+            run_id,
+            created as u64,
+            &app_hash,
+            &config,
+            &status,
+            traces,
+        )))
+    }
+
+    async fn store_run(&self, project: &Project, run: &Run) -> Result<()> {
+        let c = self.pool.get().await?;
+        c.execute(
+            // `status` is the native `job_status` enum; bind the label as text
+            // and cast it so `ToSql for &str` is accepted.
+            "INSERT INTO runs (project, run_id, created, app_hash, config, status, run_status, traces)
+             VALUES ($1, $2, $3, $4, $5, $6::text::job_status, $7, $8)
+             ON CONFLICT (project, run_id) DO UPDATE SET
+                 status = EXCLUDED.status,
+                 run_status = EXCLUDED.run_status,
+                 traces = EXCLUDED.traces",
+            &[
+                &project.project_id(),
+                &run.run_id(),
+                &(run.created() as i64),
+                &run.app_hash(),
+                &serde_json::to_value(run.config())?,
+                &status_enum_label(&run.status().run_status()),
+                &serde_json::to_value(run.status())?,
+                &serde_json::to_value(&run.traces)?,
+            ],
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn all_runs(
+        &self,
+        project: &Project,
+    ) -> Result<Vec<(String, u64, String, RunConfig)>> {
+        let c = self.pool.get().await?;
+        let rows = c
+            .query(
+                "SELECT run_id, created, app_hash, config
+                 FROM runs WHERE project = $1 ORDER BY created DESC",
+                &[&project.project_id()],
+            )
+            .await?;
+        rows.into_iter()
+            .map(|r| {
+                let config: RunConfig = serde_json::from_value(r.get::<_, Value>(3))?;
+                Ok((
+                    r.get::<_, String>(0),
+                    r.get::<_, i64>(1) as u64,
+                    r.get::<_, String>(2),
+                    config,
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Status-to-enum coercion kept next to the schema it mirrors.
+pub fn status_enum_label(status: &Status) -> &'static str {
+    match status {
+        Status::Queued => "queued",
+        Status::Running => "running",
+        Status::Succeeded => "succeeded",
+        Status::Errored => "errored",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enum_labels_match_status_to_string() {
+        // The bound label must match the job_status enum variants, which in
+        // turn mirror Status::to_string.
+        for s in [
+            Status::Queued,
+            Status::Running,
+            Status::Succeeded,
+            Status::Errored,
+        ] {
+            assert_eq!(status_enum_label(&s), s.to_string());
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use crate::blocks::block::BlockType;
+use crate::project::Project;
+use crate::artifacts::{hydrate_traces, ArtifactStore};
+use crate::run::{BlockExecution, Run, RunConfig, RunStatus, Status};
+use crate::run_queue::{JobQueue, JobStatus, QueuedJob};
+use crate::stores::store::Store;
+use crate::utils;
+use anyhow::Result;
+use async_trait::async_trait;
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::params;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Default pool size. Sized to the maximum per-block concurrency
+/// (`RunConfig::concurrency_for_block`) so persistence from a fully parallel
+/// Map/Reduce run never has to wait on a free connection.
+pub const DEFAULT_POOL_SIZE: u32 = 64;
+
+/// Busy timeout applied to every pooled connection so a writer briefly blocks
+/// rather than failing with `SQLITE_BUSY` under contention.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// SQLite-backed `Store`. Reads and writes check out a connection from an
+/// r2d2 pool, so the parallel block executions authorized by
+/// `concurrency_for_block` no longer serialize through a single handle. WAL
+/// mode lets readers proceed concurrently with the writer.
+#[derive(Clone)]
+pub struct SQLiteStore {
+    pool: Pool<SqliteConnectionManager>,
+    // Used to hydrate offloaded `ArtifactRef`s when `load_run` is asked for a
+    // fully-materialized run; `None` means references are returned as-is.
+    artifacts: Option<Arc<dyn ArtifactStore>>,
+}
+
+impl SQLiteStore {
+    pub fn new(sqlite_path: PathBuf) -> Result<Self> {
+        Self::new_with_pool_size(sqlite_path, DEFAULT_POOL_SIZE)
+    }
+
+    pub fn new_with_pool_size(sqlite_path: PathBuf, pool_size: u32) -> Result<Self> {
+        let manager = SqliteConnectionManager::file(sqlite_path).with_init(|c| {
+            c.busy_timeout(BUSY_TIMEOUT)?;
+            c.pragma_update(None, "journal_mode", "WAL")?;
+            c.pragma_update(None, "synchronous", "NORMAL")?;
+            Ok(())
+        });
+        let pool = Pool::builder().max_size(pool_size).build(manager)?;
+        Ok(SQLiteStore {
+            pool,
+            artifacts: None,
+        })
+    }
+
+    pub fn with_artifacts(mut self, artifacts: Arc<dyn ArtifactStore>) -> Self {
+        self.artifacts = Some(artifacts);
+        self
+    }
+}
+
+#[async_trait]
+impl Store for SQLiteStore {
+    async fn init(&self) -> Result<()> {
+        let c = self.pool.get()?;
+        c.execute_batch(
+            "CREATE TABLE IF NOT EXISTS runs (
+                 id         INTEGER PRIMARY KEY,
+                 project    BIGINT NOT NULL,
+                 run_id     TEXT NOT NULL,
+                 created    BIGINT NOT NULL,
+                 app_hash   TEXT NOT NULL,
+                 config     TEXT NOT NULL,
+                 status     TEXT NOT NULL,
+                 run_status TEXT NOT NULL,
+                 traces     TEXT NOT NULL,
+                 UNIQUE (project, run_id)
+             );
+             CREATE INDEX IF NOT EXISTS runs_project_created ON runs (project, created DESC);
+             CREATE TABLE IF NOT EXISTS job_queue (
+                 id         INTEGER PRIMARY KEY,
+                 run_id     TEXT NOT NULL UNIQUE,
+                 queue_name TEXT NOT NULL,
+                 status     TEXT NOT NULL,
+                 created    BIGINT NOT NULL,
+                 heartbeat  BIGINT NOT NULL,
+                 claimed_by TEXT
+             );
+             CREATE INDEX IF NOT EXISTS job_queue_status_created
+                 ON job_queue (queue_name, status, created);",
+        )?;
+        Ok(())
+    }
+
+    async fn latest_run_id(&self, project: &Project) -> Result<Option<String>> {
+        let c = self.pool.get()?;
+        match c.query_row(
+            "SELECT run_id FROM runs WHERE project = ?1 ORDER BY created DESC LIMIT 1",
+            params![project.project_id()],
+            |row| row.get::<_, String>(0),
+        ) {
+            Ok(run_id) => Ok(Some(run_id)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e)?,
+        }
+    }
+
+    async fn load_run(
+        &self,
+        project: &Project,
+        run_id: &str,
+        block: Option<Option<(BlockType, String)>>,
+        hydrate: bool,
+    ) -> Result<Option<Run>> {
+        let c = self.pool.get()?;
+        let row = c.query_row(
+            "SELECT created, app_hash, config, run_status, traces
+             FROM runs WHERE project = ?1 AND run_id = ?2",
+            params![project.project_id(), run_id],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                ))
+            },
+        );
+
+        let (created, app_hash, config, run_status, traces) = match row {
+            Ok(r) => r,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => Err(e)?,
+        };
+
+        let config: RunConfig = serde_json::from_str(&config)?;
+        let status: RunStatus = serde_json::from_str(&run_status)?;
+        let mut traces: Vec<((BlockType, String), Vec<Vec<BlockExecution>>)> =
+            serde_json::from_str(&traces)?;
+
+        if let Some(filter) = block {
+            traces.retain(|((t, n), _)| match &filter {
+                Some((bt, bn)) => t == bt && n == bn,
+                None => false,
+            });
+        }
+
+        if hydrate {
+            if let Some(artifacts) = &self.artifacts {
+                hydrate_traces(artifacts.as_ref(), &mut traces).await?;
+            }
+        }
+
+        Ok(Some(Run::new_from_store(
+            run_id,
+            created as u64,
+            &app_hash,
+            &config,
+            &status,
+            traces,
+        )))
+    }
+
+    async fn store_run(&self, project: &Project, run: &Run) -> Result<()> {
+        let c = self.pool.get()?;
+        c.execute(
+            "INSERT INTO runs (project, run_id, created, app_hash, config, status, run_status, traces)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT (project, run_id) DO UPDATE SET
+                 status = excluded.status,
+                 run_status = excluded.run_status,
+                 traces = excluded.traces",
+            params![
+                project.project_id(),
+                run.run_id(),
+                run.created() as i64,
+                run.app_hash(),
+                serde_json::to_string(run.config())?,
+                run.status().run_status().to_string(),
+                serde_json::to_string(run.status())?,
+                serde_json::to_string(&run.traces)?,
+            ],
+        )?;
+        Ok(())
+    }
+
+    async fn all_runs(&self, project: &Project) -> Result<Vec<(String, u64, String, RunConfig)>> {
+        let c = self.pool.get()?;
+        let mut stmt = c.prepare(
+            "SELECT run_id, created, app_hash, config
+             FROM runs WHERE project = ?1 ORDER BY created DESC",
+        )?;
+        let rows = stmt.query_map(params![project.project_id()], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)? as u64,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?;
+
+        let mut runs = vec![];
+        for row in rows {
+            let (run_id, created, app_hash, config) = row?;
+            let config: RunConfig = serde_json::from_str(&config)?;
+            runs.push((run_id, created, app_hash, config));
+        }
+        Ok(runs)
+    }
+}
+
+/// Columns selected when materializing a `QueuedJob` in `claim`.
+const JOB_COLUMNS: &str = "run_id, queue_name, status, heartbeat, claimed_by";
+
+/// Heartbeat cutoff (epoch millis) below which a `running` job is stale.
+/// `now_ms` and the stored `heartbeat` are milliseconds; the threshold is
+/// seconds, hence the `* 1000`.
+fn stale_cutoff(now_ms: i64, stale_after_seconds: u64) -> i64 {
+    now_ms - (stale_after_seconds as i64) * 1000
+}
+
+fn job_from_row(row: &rusqlite::Row) -> rusqlite::Result<QueuedJob> {
+    let status: String = row.get(2)?;
+    Ok(QueuedJob {
+        run_id: row.get(0)?,
+        queue_name: row.get(1)?,
+        status: JobStatus::from_str(&status)
+            .map_err(|_| rusqlite::Error::InvalidQuery)?,
+        heartbeat: row.get::<_, i64>(3)? as u64,
+        claimed_by: row.get(4)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_cutoff_subtracts_seconds_in_millis() {
+        // 60s threshold at t=100_000ms => anything before 40_000ms is stale.
+        assert_eq!(stale_cutoff(100_000, 60), 40_000);
+        assert_eq!(stale_cutoff(100_000, 0), 100_000);
+    }
+
+    #[test]
+    fn default_pool_size_matches_max_block_concurrency() {
+        // The pool must be at least as large as the highest per-block
+        // concurrency so parallel trace writes never starve on connections.
+        assert_eq!(DEFAULT_POOL_SIZE, 64);
+    }
+}
+
+#[async_trait]
+impl JobQueue for SQLiteStore {
+    async fn enqueue(&self, project: &Project, run: &Run, queue_name: &str) -> Result<()> {
+        // Persist the run as `Queued` and register a `new` job pointing at it.
+        let mut run = run.clone();
+        run.set_run_status(Status::Queued);
+        self.store_run(project, &run).await?;
+
+        let c = self.pool.get()?;
+        let now = utils::now() as i64;
+        c.execute(
+            "INSERT INTO job_queue (run_id, queue_name, status, created, heartbeat, claimed_by)
+             VALUES (?1, ?2, 'new', ?3, 0, NULL)
+             ON CONFLICT (run_id) DO NOTHING",
+            params![run.run_id(), queue_name, now],
+        )?;
+        Ok(())
+    }
+
+    async fn claim(&self, queue_name: &str, worker_id: &str) -> Result<Option<QueuedJob>> {
+        let c = self.pool.get()?;
+        let now = utils::now() as i64;
+        // Atomic claim of the oldest `new` job on this queue. The single
+        // `UPDATE ... RETURNING` statement is transactional in SQLite. An empty
+        // queue returns no rows; any other error (lock contention, corruption)
+        // propagates rather than masquerading as an empty queue.
+        match c.query_row(
+            &format!(
+                "UPDATE job_queue SET status = 'running', heartbeat = ?1, claimed_by = ?2
+                 WHERE run_id = (
+                     SELECT run_id FROM job_queue
+                     WHERE queue_name = ?3 AND status = 'new'
+                     ORDER BY created ASC LIMIT 1
+                 )
+                 RETURNING {}",
+                JOB_COLUMNS
+            ),
+            params![now, worker_id, queue_name],
+            job_from_row,
+        ) {
+            Ok(job) => Ok(Some(job)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e)?,
+        }
+    }
+
+    async fn heartbeat(&self, run_id: &str) -> Result<()> {
+        let c = self.pool.get()?;
+        c.execute(
+            "UPDATE job_queue SET heartbeat = ?1 WHERE run_id = ?2 AND status = 'running'",
+            params![utils::now() as i64, run_id],
+        )?;
+        Ok(())
+    }
+
+    async fn complete(&self, run_id: &str) -> Result<()> {
+        let c = self.pool.get()?;
+        c.execute("DELETE FROM job_queue WHERE run_id = ?1", params![run_id])?;
+        Ok(())
+    }
+
+    async fn reap(&self, stale_after_seconds: u64) -> Result<Vec<String>> {
+        let c = self.pool.get()?;
+        let cutoff = stale_cutoff(utils::now() as i64, stale_after_seconds);
+        let mut stmt = c.prepare(
+            "UPDATE job_queue SET status = 'new', claimed_by = NULL
+             WHERE status = 'running' AND heartbeat < ?1
+             RETURNING run_id",
+        )?;
+        let rows = stmt.query_map(params![cutoff], |row| row.get::<_, String>(0))?;
+        let mut recovered = vec![];
+        for run_id in rows {
+            recovered.push(run_id?);
+        }
+        Ok(recovered)
+    }
+}